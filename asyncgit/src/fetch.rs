@@ -0,0 +1,150 @@
+use crate::{
+    error::Result, progress::AsyncJob, sync, AsyncNotification, CWD,
+};
+use crossbeam_channel::Sender;
+use git2::PackBuilderStage;
+use std::{
+    cmp,
+    sync::{atomic::AtomicBool, Arc},
+};
+use sync::ProgressNotification;
+
+///
+#[derive(Clone, Debug)]
+pub enum FetchProgressState {
+    ///
+    PackingAddingObject,
+    ///
+    PackingDeltafiction,
+    ///
+    Transfer,
+    /// an abort was requested and the fetch is being torn down
+    Cancelling,
+}
+
+///
+#[derive(Clone, Debug)]
+pub struct FetchProgress {
+    ///
+    pub state: FetchProgressState,
+    ///
+    pub progress: u8,
+}
+
+impl FetchProgress {
+    ///
+    pub fn new(
+        state: FetchProgressState,
+        current: usize,
+        total: usize,
+    ) -> Self {
+        let total = cmp::max(current, total) as f32;
+        let progress = current as f32 / total * 100.0;
+        let progress = progress as u8;
+        Self { state, progress }
+    }
+}
+
+impl From<ProgressNotification> for FetchProgress {
+    fn from(progress: ProgressNotification) -> Self {
+        match progress {
+            ProgressNotification::Packing {
+                stage,
+                current,
+                total,
+            } => match stage {
+                PackBuilderStage::AddingObjects => FetchProgress::new(
+                    FetchProgressState::PackingAddingObject,
+                    current,
+                    total,
+                ),
+                PackBuilderStage::Deltafication => FetchProgress::new(
+                    FetchProgressState::PackingDeltafiction,
+                    current,
+                    total,
+                ),
+            },
+            ProgressNotification::Transfer {
+                current, total, ..
+            } => FetchProgress::new(
+                FetchProgressState::Transfer,
+                current,
+                total,
+            ),
+            _ => FetchProgress::new(FetchProgressState::Transfer, 1, 1),
+        }
+    }
+}
+
+///
+#[derive(Default, Clone, Debug)]
+pub struct FetchRequest {
+    ///
+    pub remote: String,
+    ///
+    pub branch: String,
+}
+
+///
+pub struct AsyncFetch {
+    job: AsyncJob<FetchRequest, FetchProgress>,
+}
+
+impl AsyncFetch {
+    ///
+    pub fn new(sender: &Sender<AsyncNotification>) -> Self {
+        Self {
+            job: AsyncJob::new(
+                sender,
+                AsyncNotification::Fetch,
+                |params: &FetchRequest,
+                 progress_sender,
+                 abort: Arc<AtomicBool>| {
+                    sync::fetch(
+                        CWD,
+                        params.remote.as_str(),
+                        params.branch.as_str(),
+                        progress_sender,
+                        abort,
+                    )
+                },
+            ),
+        }
+    }
+
+    ///
+    pub fn is_pending(&self) -> Result<bool> {
+        self.job.is_pending()
+    }
+
+    /// Request an abort of the in-flight fetch.
+    pub fn cancel(&mut self) -> Result<()> {
+        self.job.cancel()
+    }
+
+    ///
+    pub fn last_result(&self) -> Result<Option<String>> {
+        self.job.last_result()
+    }
+
+    ///
+    pub fn progress(&self) -> Result<Option<FetchProgress>> {
+        let mut progress = self.job.progress()?;
+
+        // surface the teardown once an abort has been requested,
+        // even if no progress has been reported yet
+        if self.job.is_cancelling() {
+            let progress = progress.get_or_insert_with(|| {
+                FetchProgress::new(FetchProgressState::Cancelling, 0, 0)
+            });
+            progress.state = FetchProgressState::Cancelling;
+        }
+
+        Ok(progress)
+    }
+
+    ///
+    pub fn request(&mut self, params: FetchRequest) -> Result<()> {
+        self.job.request(params)
+    }
+}
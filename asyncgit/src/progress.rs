@@ -0,0 +1,244 @@
+use crate::{error::Result, AsyncNotification};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+use sync::ProgressNotification;
+
+/// Runs the actual blocking git operation, feeding raw progress into
+/// `progress_sender` and bailing out when the shared `abort` flag is
+/// set (the git2 callbacks watch the flag).
+pub type JobFn<Req> = fn(
+    &Req,
+    Sender<ProgressNotification>,
+    Arc<AtomicBool>,
+) -> Result<()>;
+
+/// Reusable engine behind every long-running git network operation
+/// (push/fetch/clone/pull): it owns the pending-request, result and
+/// raw-progress trio, spawns the worker plus the progress-forwarding
+/// receiver loop and carries the shared abort flag, so each operation
+/// gets consistent streaming progress, error capture and cancellation.
+///
+/// `Req` is the request payload, `Prog` the display-progress type the
+/// raw `ProgressNotification` is mapped into (e.g. `PushProgress`).
+pub struct AsyncJob<Req, Prog>
+where
+    Req: Clone + Send + 'static,
+    Prog: From<ProgressNotification>,
+{
+    state: Arc<Mutex<Option<Req>>>,
+    last_result: Arc<Mutex<Option<String>>>,
+    progress: Arc<Mutex<Option<ProgressNotification>>>,
+    abort: Arc<AtomicBool>,
+    sender: Sender<AsyncNotification>,
+    notification: AsyncNotification,
+    run: JobFn<Req>,
+    _prog: PhantomData<Prog>,
+}
+
+impl<Req, Prog> AsyncJob<Req, Prog>
+where
+    Req: Clone + Send + 'static,
+    Prog: From<ProgressNotification>,
+{
+    /// `notification` is the `AsyncNotification` variant emitted on
+    /// every progress update and on completion; `run` performs the op.
+    pub fn new(
+        sender: &Sender<AsyncNotification>,
+        notification: AsyncNotification,
+        run: JobFn<Req>,
+    ) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(None)),
+            last_result: Arc::new(Mutex::new(None)),
+            progress: Arc::new(Mutex::new(None)),
+            abort: Arc::new(AtomicBool::new(false)),
+            sender: sender.clone(),
+            notification,
+            run,
+            _prog: PhantomData,
+        }
+    }
+
+    ///
+    pub fn is_pending(&self) -> Result<bool> {
+        let state = self.state.lock()?;
+        Ok(state.is_some())
+    }
+
+    /// Whether an abort has been requested for the in-flight job.
+    pub fn is_cancelling(&self) -> bool {
+        self.abort.load(Ordering::Relaxed)
+    }
+
+    /// Request an abort of the in-flight job. The worker's git2
+    /// callbacks pick up the flag and make libgit2 bail out; teardown
+    /// then follows the normal completion path.
+    pub fn cancel(&mut self) -> Result<()> {
+        if self.is_pending()? {
+            self.abort.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    ///
+    pub fn last_result(&self) -> Result<Option<String>> {
+        let res = self.last_result.lock()?;
+        Ok(res.clone())
+    }
+
+    ///
+    pub fn progress(&self) -> Result<Option<Prog>> {
+        let res = self.progress.lock()?;
+        Ok(res.as_ref().map(|progress| progress.clone().into()))
+    }
+
+    ///
+    pub fn request(&mut self, params: Req) -> Result<()> {
+        log::trace!("request");
+
+        if self.is_pending()? {
+            return Ok(());
+        }
+
+        self.set_request(&params)?;
+        Self::set_progress(self.progress.clone(), None)?;
+        self.abort.store(false, Ordering::Relaxed);
+
+        let arc_state = Arc::clone(&self.state);
+        let arc_res = Arc::clone(&self.last_result);
+        let arc_progress = Arc::clone(&self.progress);
+        let arc_abort = Arc::clone(&self.abort);
+        let sender = self.sender.clone();
+        let notification = self.notification.clone();
+        let run = self.run;
+
+        thread::spawn(move || {
+            let (progress_sender, receiver) = unbounded();
+
+            let handle = Self::spawn_receiver_thread(
+                notification.clone(),
+                sender.clone(),
+                receiver,
+                arc_progress,
+            );
+
+            let res =
+                run(&params, progress_sender.clone(), arc_abort.clone());
+
+            progress_sender
+                .send(ProgressNotification::Done)
+                .expect("closing send failed");
+
+            handle.join().expect("joining thread failed");
+
+            Self::set_result(arc_res, res, &arc_abort)
+                .expect("result error");
+
+            Self::clear_request(arc_state).expect("clear error");
+
+            arc_abort.store(false, Ordering::Relaxed);
+
+            sender
+                .send(notification)
+                .expect("error sending notification");
+        });
+
+        Ok(())
+    }
+
+    fn spawn_receiver_thread(
+        notification: AsyncNotification,
+        sender: Sender<AsyncNotification>,
+        receiver: Receiver<ProgressNotification>,
+        progress: Arc<Mutex<Option<ProgressNotification>>>,
+    ) -> thread::JoinHandle<()> {
+        log::info!("progress receiver spawned");
+
+        thread::spawn(move || loop {
+            let incoming = receiver.recv();
+            match incoming {
+                Ok(update) => {
+                    Self::set_progress(
+                        progress.clone(),
+                        Some(update.clone()),
+                    )
+                    .expect("set progress failed");
+                    sender
+                        .send(notification.clone())
+                        .expect("error sending notification");
+
+                    if let ProgressNotification::Done = update {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::error!("progress receiver error: {}", e);
+                    break;
+                }
+            }
+        })
+    }
+
+    fn set_request(&self, params: &Req) -> Result<()> {
+        let mut state = self.state.lock()?;
+
+        if state.is_some() {
+            return Err(crate::error::Error::Generic(
+                "pending request".into(),
+            ));
+        }
+
+        *state = Some(params.clone());
+
+        Ok(())
+    }
+
+    fn clear_request(state: Arc<Mutex<Option<Req>>>) -> Result<()> {
+        let mut state = state.lock()?;
+
+        *state = None;
+
+        Ok(())
+    }
+
+    fn set_progress(
+        progress: Arc<Mutex<Option<ProgressNotification>>>,
+        state: Option<ProgressNotification>,
+    ) -> Result<()> {
+        let mut progress = progress.lock()?;
+
+        *progress = state;
+
+        Ok(())
+    }
+
+    fn set_result(
+        arc_result: Arc<Mutex<Option<String>>>,
+        res: Result<()>,
+        abort: &Arc<AtomicBool>,
+    ) -> Result<()> {
+        let mut last_res = arc_result.lock()?;
+
+        *last_res = match res {
+            Ok(_) => None,
+            // a user-requested cancel surfaces as a libgit2 error from
+            // the aborted callback; treat it as a clean completion
+            // rather than a job failure
+            Err(_) if abort.load(Ordering::Relaxed) => None,
+            Err(e) => {
+                log::error!("async job error: {}", e);
+                Some(e.to_string())
+            }
+        };
+
+        Ok(())
+    }
+}
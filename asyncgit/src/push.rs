@@ -1,17 +1,13 @@
 use crate::{
-    error::{Error, Result},
-    sync, AsyncNotification, CWD,
+    error::Result, progress::AsyncJob, sync, AsyncNotification, CWD,
 };
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use crossbeam_channel::Sender;
 use git2::PackBuilderStage;
 use std::{
     cmp,
-    sync::{Arc, Mutex},
-    thread,
-    time::Duration,
+    sync::{atomic::AtomicBool, Arc},
 };
 use sync::ProgressNotification;
-use thread::JoinHandle;
 
 ///
 #[derive(Clone, Debug)]
@@ -22,6 +18,8 @@ pub enum PushProgressState {
     PackingDeltafiction,
     ///
     Pushing,
+    /// an abort was requested and the push is being torn down
+    Cancelling,
 }
 
 ///
@@ -90,189 +88,67 @@ pub struct PushRequest {
     pub branch: String,
 }
 
-#[derive(Default, Clone, Debug)]
-struct PushState {
-    request: PushRequest,
-}
-
 ///
 pub struct AsyncPush {
-    state: Arc<Mutex<Option<PushState>>>,
-    last_result: Arc<Mutex<Option<String>>>,
-    progress: Arc<Mutex<Option<ProgressNotification>>>,
-    sender: Sender<AsyncNotification>,
+    job: AsyncJob<PushRequest, PushProgress>,
 }
 
 impl AsyncPush {
     ///
     pub fn new(sender: &Sender<AsyncNotification>) -> Self {
         Self {
-            state: Arc::new(Mutex::new(None)),
-            last_result: Arc::new(Mutex::new(None)),
-            progress: Arc::new(Mutex::new(None)),
-            sender: sender.clone(),
+            job: AsyncJob::new(
+                sender,
+                AsyncNotification::Push,
+                |params: &PushRequest,
+                 progress_sender,
+                 abort: Arc<AtomicBool>| {
+                    sync::push(
+                        CWD,
+                        params.remote.as_str(),
+                        params.branch.as_str(),
+                        progress_sender,
+                        abort,
+                    )
+                },
+            ),
         }
     }
 
     ///
     pub fn is_pending(&self) -> Result<bool> {
-        let state = self.state.lock()?;
-        Ok(state.is_some())
+        self.job.is_pending()
     }
 
-    ///
-    pub fn last_result(&self) -> Result<Option<String>> {
-        let res = self.last_result.lock()?;
-        Ok(res.clone())
+    /// Request an abort of the in-flight push.
+    pub fn cancel(&mut self) -> Result<()> {
+        self.job.cancel()
     }
 
     ///
-    pub fn progress(&self) -> Result<Option<PushProgress>> {
-        let res = self.progress.lock()?;
-        Ok(res.as_ref().map(|progress| progress.clone().into()))
+    pub fn last_result(&self) -> Result<Option<String>> {
+        self.job.last_result()
     }
 
     ///
-    pub fn request(&mut self, params: PushRequest) -> Result<()> {
-        log::trace!("request");
-
-        if self.is_pending()? {
-            return Ok(());
-        }
-
-        self.set_request(&params)?;
-        Self::set_progress(self.progress.clone(), None)?;
-
-        let arc_state = Arc::clone(&self.state);
-        let arc_res = Arc::clone(&self.last_result);
-        let arc_progress = Arc::clone(&self.progress);
-        let sender = self.sender.clone();
-
-        thread::spawn(move || {
-            let (progress_sender, receiver) = unbounded();
-
-            let handle = Self::spawn_receiver_thread(
-                sender.clone(),
-                receiver,
-                arc_progress,
-            );
-
-            let res = sync::push(
-                CWD,
-                params.remote.as_str(),
-                params.branch.as_str(),
-                progress_sender.clone(),
-            );
-
-            progress_sender
-                .send(ProgressNotification::Done)
-                .expect("closing send failed");
-
-            handle.join().expect("joining thread failed");
-
-            Self::set_result(arc_res, res).expect("result error");
-
-            Self::clear_request(arc_state).expect("clear error");
-
-            sender
-                .send(AsyncNotification::Push)
-                .expect("error sending push");
-        });
-
-        Ok(())
-    }
-
-    fn spawn_receiver_thread(
-        sender: Sender<AsyncNotification>,
-        receiver: Receiver<ProgressNotification>,
-        progress: Arc<Mutex<Option<ProgressNotification>>>,
-    ) -> JoinHandle<()> {
-        log::info!("push progress receiver spawned");
-
-        thread::spawn(move || loop {
-            let incoming = receiver.recv();
-            match incoming {
-                Ok(update) => {
-                    Self::set_progress(
-                        progress.clone(),
-                        Some(update.clone()),
-                    )
-                    .expect("set prgoress failed");
-                    sender
-                        .send(AsyncNotification::Push)
-                        .expect("error sending push");
-
-                    //NOTE: for better debugging
-                    thread::sleep(Duration::from_millis(300));
-
-                    if let ProgressNotification::Done = update {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    log::error!(
-                        "push progress receiver error: {}",
-                        e
-                    );
-                    break;
-                }
-            }
-        })
-    }
-
-    fn set_request(&self, params: &PushRequest) -> Result<()> {
-        let mut state = self.state.lock()?;
-
-        if state.is_some() {
-            return Err(Error::Generic("pending request".into()));
+    pub fn progress(&self) -> Result<Option<PushProgress>> {
+        let mut progress = self.job.progress()?;
+
+        // surface the teardown once an abort has been requested,
+        // even if no progress has been reported yet
+        if self.job.is_cancelling() {
+            let progress = progress.get_or_insert_with(|| {
+                PushProgress::new(PushProgressState::Cancelling, 0, 0)
+            });
+            progress.state = PushProgressState::Cancelling;
         }
 
-        *state = Some(PushState {
-            request: params.clone(),
-        });
-
-        Ok(())
+        Ok(progress)
     }
 
-    fn clear_request(
-        state: Arc<Mutex<Option<PushState>>>,
-    ) -> Result<()> {
-        let mut state = state.lock()?;
-
-        *state = None;
-
-        Ok(())
-    }
-
-    fn set_progress(
-        progress: Arc<Mutex<Option<ProgressNotification>>>,
-        state: Option<ProgressNotification>,
-    ) -> Result<()> {
-        let simple_progress: Option<PushProgress> =
-            state.as_ref().map(|prog| prog.clone().into());
-        log::info!("push progress: {:?}", simple_progress);
-        let mut progress = progress.lock()?;
-
-        *progress = state;
-
-        Ok(())
-    }
-
-    fn set_result(
-        arc_result: Arc<Mutex<Option<String>>>,
-        res: Result<()>,
-    ) -> Result<()> {
-        let mut last_res = arc_result.lock()?;
-
-        *last_res = match res {
-            Ok(_) => None,
-            Err(e) => {
-                log::error!("push error: {}", e);
-                Some(e.to_string())
-            }
-        };
-
-        Ok(())
+    ///
+    pub fn request(&mut self, params: PushRequest) -> Result<()> {
+        self.job.request(params)
     }
 }
 
@@ -9,10 +9,16 @@ use crate::{
 };
 use anyhow::Result;
 use crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::cmp;
 use tui::{
     backend::Backend, layout::Rect, style::Modifier, text::Span,
     widgets::Clear, Frame,
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// maximum amount of previously submitted texts kept around for recall
+const HISTORY_LENGTH: usize = 50;
 
 /// primarily a subcomponet for user input of text (used in `CommitComponent`)
 pub struct TextInputComponent {
@@ -23,6 +29,10 @@ pub struct TextInputComponent {
     theme: SharedTheme,
     key_config: SharedKeyConfig,
     cursor_position: usize,
+    history: Vec<String>,
+    history_cursor: usize,
+    draft: String,
+    kill_ring: String,
 }
 
 impl TextInputComponent {
@@ -41,9 +51,66 @@ impl TextInputComponent {
             title: title.to_string(),
             default_msg: default_msg.to_string(),
             cursor_position: 0,
+            history: Vec::new(),
+            history_cursor: 0,
+            draft: String::default(),
+            kill_ring: String::default(),
         }
     }
 
+    /// Push a submitted `text` onto the input history so it can be
+    /// recalled later with `Up`/`Down`. Empty texts and duplicates of
+    /// the most recent entry are ignored, and the ring is bounded to
+    /// `HISTORY_LENGTH` entries.
+    pub fn commit_history_push(&mut self, text: String) {
+        if text.is_empty()
+            || self.history.last().map_or(false, |last| *last == text)
+        {
+            return;
+        }
+
+        self.history.push(text);
+
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.remove(0);
+        }
+    }
+
+    /// Move the history cursor back to an older entry, loading it into
+    /// `msg`. The live draft is stashed the first time we leave it so
+    /// `Down` can return to it.
+    fn history_back(&mut self) {
+        if self.history.is_empty() || self.history_cursor == 0 {
+            return;
+        }
+
+        if self.history_cursor == self.history.len() {
+            self.draft = self.msg.clone();
+        }
+
+        self.history_cursor -= 1;
+        self.set_text(self.history[self.history_cursor].clone());
+        self.cursor_position = self.msg.len();
+    }
+
+    /// Move the history cursor forward towards the live draft, loading
+    /// the entry (or the stashed draft) into `msg`.
+    fn history_forward(&mut self) {
+        if self.history_cursor >= self.history.len() {
+            return;
+        }
+
+        self.history_cursor += 1;
+        let text = if self.history_cursor == self.history.len() {
+            self.draft.clone()
+        } else {
+            self.history[self.history_cursor].clone()
+        };
+
+        self.set_text(text);
+        self.cursor_position = self.msg.len();
+    }
+
     /// Clear the `msg`.
     pub fn clear(&mut self) {
         self.msg.clear();
@@ -62,35 +129,124 @@ impl TextInputComponent {
         }
     }
 
-    /// Move the cursor left one char.
+    /// Move the cursor left one grapheme cluster.
     fn decr_cursor(&mut self) {
-        let mut index = self.cursor_position.saturating_sub(1);
-        while index > 0 && !self.msg.is_char_boundary(index) {
-            index -= 1;
-        }
-        self.cursor_position = index;
+        self.cursor_position = self
+            .msg
+            .grapheme_indices(true)
+            .take_while(|(i, _)| *i < self.cursor_position)
+            .last()
+            .map_or(0, |(i, _)| i);
     }
 
-    /// Get the position of the next char, or, if the cursor points
-    /// to the last char, the `msg.len()`.
+    /// Get the byte position past the grapheme at the cursor, or, if
+    /// the cursor points to the last grapheme, the `msg.len()`.
     /// Returns None when the cursor is already at `msg.len()`.
     fn next_char_position(&self) -> Option<usize> {
         if self.cursor_position >= self.msg.len() {
             return None;
         }
-        let mut index = self.cursor_position.saturating_add(1);
-        while index < self.msg.len()
-            && !self.msg.is_char_boundary(index)
-        {
-            index += 1;
+        self.msg[self.cursor_position..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| self.cursor_position + grapheme.len())
+    }
+
+    /// The char (and its start byte) immediately left of `index`,
+    /// or `None` at the start of `msg`.
+    fn char_before(&self, index: usize) -> Option<(usize, char)> {
+        self.msg[..index].char_indices().next_back()
+    }
+
+    /// The char (and its start byte) at `index`, or `None` at the end
+    /// of `msg`.
+    fn char_after(&self, index: usize) -> Option<(usize, char)> {
+        self.msg[index..].chars().next().map(|c| (index, c))
+    }
+
+    /// Byte position one word to the left of the cursor: skip any
+    /// non-alphanumeric chars then consume the alphanumeric run.
+    fn prev_word_position(&self) -> usize {
+        let mut index = self.cursor_position;
+        while let Some((i, c)) = self.char_before(index) {
+            if c.is_alphanumeric() {
+                break;
+            }
+            index = i;
+        }
+        while let Some((i, c)) = self.char_before(index) {
+            if !c.is_alphanumeric() {
+                break;
+            }
+            index = i;
+        }
+        index
+    }
+
+    /// Byte position one word to the right of the cursor: skip any
+    /// non-alphanumeric chars then consume the alphanumeric run.
+    fn next_word_position(&self) -> usize {
+        let mut index = self.cursor_position;
+        while let Some((i, c)) = self.char_after(index) {
+            if c.is_alphanumeric() {
+                break;
+            }
+            index = i + c.len_utf8();
+        }
+        while let Some((i, c)) = self.char_after(index) {
+            if !c.is_alphanumeric() {
+                break;
+            }
+            index = i + c.len_utf8();
         }
-        Some(index)
+        index
+    }
+
+    /// Kill the word before the cursor into the kill ring.
+    fn delete_word(&mut self) {
+        let start = self.prev_word_position();
+        self.kill_ring =
+            self.msg[start..self.cursor_position].to_string();
+        self.msg.replace_range(start..self.cursor_position, "");
+        self.cursor_position = start;
+    }
+
+    /// Kill from the cursor to the end of the line into the kill ring.
+    fn kill_to_end(&mut self) {
+        let end = self.msg[self.cursor_position..]
+            .find('\n')
+            .map_or(self.msg.len(), |i| self.cursor_position + i);
+        self.kill_ring =
+            self.msg[self.cursor_position..end].to_string();
+        self.msg.replace_range(self.cursor_position..end, "");
+    }
+
+    /// Kill from the start of the line to the cursor into the kill ring.
+    fn kill_to_start(&mut self) {
+        let start = self.msg[..self.cursor_position]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        self.kill_ring =
+            self.msg[start..self.cursor_position].to_string();
+        self.msg.replace_range(start..self.cursor_position, "");
+        self.cursor_position = start;
+    }
+
+    /// Reinsert the most recently killed text at the cursor.
+    fn yank(&mut self) {
+        if self.kill_ring.is_empty() {
+            return;
+        }
+        let yanked = self.kill_ring.clone();
+        self.msg.insert_str(self.cursor_position, &yanked);
+        self.cursor_position += yanked.len();
     }
 
     fn backspace(&mut self) {
         if self.cursor_position > 0 {
+            let end = self.cursor_position;
             self.decr_cursor();
-            self.msg.remove(self.cursor_position);
+            self.msg.replace_range(self.cursor_position..end, "");
         }
     }
 
@@ -166,8 +322,20 @@ impl DrawableComponent for TextInputComponent {
                 self.get_draw_text()
             };
 
+            // size the popup to the widest rendered line so wide
+            // (CJK/emoji) and combining glyphs stay aligned, leaving a
+            // cell for the trailing cursor.
+            let width = self
+                .msg
+                .lines()
+                .map(UnicodeWidthStr::width)
+                .max()
+                .unwrap_or(0)
+                .saturating_add(1);
+            let width = cmp::max(10, width as u16);
+
             let area = ui::centered_rect(60, 20, f.size());
-            let area = ui::rect_min(10, 3, area);
+            let area = ui::rect_min(width, 3, area);
 
             f.render_widget(Clear, area);
             f.render_widget(
@@ -212,16 +380,47 @@ impl Component for TextInputComponent {
 
                 let is_ctrl =
                     e.modifiers.contains(KeyModifiers::CONTROL);
+                let is_alt =
+                    e.modifiers.contains(KeyModifiers::ALT);
 
                 match e.code {
-                    KeyCode::Char(c) if !is_ctrl => {
+                    KeyCode::Char('w') if is_ctrl => {
+                        self.delete_word();
+                        return Ok(true);
+                    }
+                    KeyCode::Char('k') if is_ctrl => {
+                        self.kill_to_end();
+                        return Ok(true);
+                    }
+                    KeyCode::Char('u') if is_ctrl => {
+                        self.kill_to_start();
+                        return Ok(true);
+                    }
+                    KeyCode::Char('y') if is_ctrl => {
+                        self.yank();
+                        return Ok(true);
+                    }
+                    KeyCode::Char('b') if is_alt => {
+                        self.cursor_position =
+                            self.prev_word_position();
+                        return Ok(true);
+                    }
+                    KeyCode::Char('f') if is_alt => {
+                        self.cursor_position =
+                            self.next_word_position();
+                        return Ok(true);
+                    }
+                    KeyCode::Char(c) if !is_ctrl && !is_alt => {
                         self.msg.insert(self.cursor_position, c);
                         self.incr_cursor();
                         return Ok(true);
                     }
                     KeyCode::Delete => {
-                        if self.cursor_position < self.msg.len() {
-                            self.msg.remove(self.cursor_position);
+                        if let Some(end) = self.next_char_position() {
+                            self.msg.replace_range(
+                                self.cursor_position..end,
+                                "",
+                            );
                         }
                         return Ok(true);
                     }
@@ -229,10 +428,20 @@ impl Component for TextInputComponent {
                         self.backspace();
                         return Ok(true);
                     }
+                    KeyCode::Left if is_ctrl => {
+                        self.cursor_position =
+                            self.prev_word_position();
+                        return Ok(true);
+                    }
                     KeyCode::Left => {
                         self.decr_cursor();
                         return Ok(true);
                     }
+                    KeyCode::Right if is_ctrl => {
+                        self.cursor_position =
+                            self.next_word_position();
+                        return Ok(true);
+                    }
                     KeyCode::Right => {
                         self.incr_cursor();
                         return Ok(true);
@@ -245,6 +454,14 @@ impl Component for TextInputComponent {
                         self.cursor_position = self.msg.len();
                         return Ok(true);
                     }
+                    KeyCode::Up => {
+                        self.history_back();
+                        return Ok(true);
+                    }
+                    KeyCode::Down => {
+                        self.history_forward();
+                        return Ok(true);
+                    }
                     _ => (),
                 };
             }
@@ -262,6 +479,8 @@ impl Component for TextInputComponent {
 
     fn show(&mut self) -> Result<()> {
         self.visible = true;
+        self.history_cursor = self.history.len();
+        self.draft.clear();
 
         Ok(())
     }
@@ -392,6 +611,128 @@ mod tests {
         assert_eq!(get_text(&txt[1]), Some("\nb"));
     }
 
+    #[test]
+    fn test_history_recall() {
+        let mut comp = TextInputComponent::new(
+            SharedTheme::default(),
+            SharedKeyConfig::default(),
+            "",
+            "",
+        );
+
+        comp.commit_history_push(String::from("first"));
+        comp.commit_history_push(String::from("second"));
+        // duplicate of the most recent entry is ignored
+        comp.commit_history_push(String::from("second"));
+
+        comp.show().unwrap();
+        comp.set_text(String::from("draft"));
+
+        comp.history_back();
+        assert_eq!(comp.get_text().as_str(), "second");
+        comp.history_back();
+        assert_eq!(comp.get_text().as_str(), "first");
+        // already at the oldest entry, stays put
+        comp.history_back();
+        assert_eq!(comp.get_text().as_str(), "first");
+
+        comp.history_forward();
+        assert_eq!(comp.get_text().as_str(), "second");
+        // returns to the stashed live draft
+        comp.history_forward();
+        assert_eq!(comp.get_text().as_str(), "draft");
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let mut comp = TextInputComponent::new(
+            SharedTheme::default(),
+            SharedKeyConfig::default(),
+            "",
+            "",
+        );
+
+        comp.set_text(String::from("foo bar baz"));
+        comp.cursor_position = comp.msg.len();
+
+        assert_eq!(comp.prev_word_position(), 8);
+        comp.cursor_position = comp.prev_word_position();
+        assert_eq!(comp.prev_word_position(), 4);
+
+        comp.cursor_position = 0;
+        assert_eq!(comp.next_word_position(), 3);
+    }
+
+    #[test]
+    fn test_kill_and_yank() {
+        let mut comp = TextInputComponent::new(
+            SharedTheme::default(),
+            SharedKeyConfig::default(),
+            "",
+            "",
+        );
+
+        comp.set_text(String::from("foo bar"));
+        comp.cursor_position = comp.msg.len();
+
+        comp.delete_word();
+        assert_eq!(comp.get_text().as_str(), "foo ");
+        assert_eq!(comp.cursor_position, 4);
+
+        comp.yank();
+        assert_eq!(comp.get_text().as_str(), "foo bar");
+
+        comp.cursor_position = 4;
+        comp.kill_to_end();
+        assert_eq!(comp.get_text().as_str(), "foo ");
+
+        comp.kill_to_start();
+        assert_eq!(comp.get_text().as_str(), "");
+    }
+
+    #[test]
+    fn test_grapheme_cursor() {
+        let mut comp = TextInputComponent::new(
+            SharedTheme::default(),
+            SharedKeyConfig::default(),
+            "",
+            "",
+        );
+
+        // a combining sequence ("e" + combining acute) is a single
+        // grapheme of 3 bytes
+        comp.set_text(String::from("e\u{0301}x"));
+
+        comp.incr_cursor();
+        assert_eq!(comp.cursor_position, 3);
+
+        comp.decr_cursor();
+        assert_eq!(comp.cursor_position, 0);
+
+        // the underlined cursor cell covers the whole grapheme
+        let txt = comp.get_draw_text();
+        assert_eq!(get_text(&txt[0]), Some("e\u{0301}"));
+    }
+
+    #[test]
+    fn test_backspace_deletes_whole_grapheme() {
+        let mut comp = TextInputComponent::new(
+            SharedTheme::default(),
+            SharedKeyConfig::default(),
+            "",
+            "",
+        );
+
+        comp.set_text(String::from("e\u{0301}x"));
+        comp.incr_cursor();
+
+        // backspace must remove the full 2-codepoint grapheme, not
+        // just the base char
+        comp.backspace();
+        assert_eq!(comp.msg, "x");
+        assert_eq!(comp.cursor_position, 0);
+    }
+
     fn get_text<'a>(t: &'a Span) -> Option<&'a str> {
         Some(&t.content)
     }